@@ -1,7 +1,10 @@
+use std::collections::HashMap;
 use std::env;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
-use reqwest::{Client, ClientBuilder, header};
+use reqwest::{Client, ClientBuilder, Method, header};
+use serde::Serialize;
 use tokio;
 use futures::future::join_all;
 
@@ -18,15 +21,123 @@ fn print_help() {
     println!("  -connectTimeOut [value] - HTTP request timeout in milliseconds. Default is 20000.");
     println!("  -reuseConnects          - Add the request 'Connection: keep-alive' header.");
     println!("  -keepConnectsOpen       - Force a new connection with every request (not advised).");
+    println!("  -method [value]         - HTTP method: GET, POST, PUT, PATCH, DELETE. Default is GET.");
+    println!("  -body [string]          - Request body sent with every call.");
+    println!("  -bodyFile [path]        - Read the request body from a file.");
+    println!("  -contentType [value]    - Value for the 'Content-Type' header.");
+    println!("  -header \"Name: Value\"    - Add a custom request header. May be repeated.");
+    println!("  -qps [value]            - Drive a constant aggregate request rate instead of as fast as possible.");
+    println!("  -duration [value]       - Run for this many seconds instead of stopping after totalCalls.");
+    println!("  -output [json|csv]      - Also emit a machine-readable summary on stdout.");
+    println!("  -csvFile [path]         - Write raw per-request records to a CSV file.");
+    println!("  -traceConn              - Per-request DNS/connect/TTFB timing. NOT YET IMPLEMENTED; deferred.");
     println!("Help:");
     println!("  -? or --help - Display this help message.");
 }
 
-async fn fetch_data(client: Client, response_times: Arc<Mutex<Vec<f64>>>, url: String, sleep_time: Duration, keep_connects_open: bool,
+struct RequestSpec {
+    method: Method,
+    headers: header::HeaderMap,
+    body: Option<Vec<u8>>,
+}
+
+#[derive(Serialize)]
+struct LatencyStats {
+    min: f64,
+    mean: f64,
+    stddev: f64,
+    max: f64,
+    p50: f64,
+    p90: f64,
+    p95: f64,
+    p99: f64,
+    p999: f64,
+}
+
+#[derive(Serialize)]
+struct Summary {
+    total_time_s: f64,
+    completed_calls: usize,
+    requests_per_second: f64,
+    latency: Option<LatencyStats>,
+    status_counts: HashMap<String, usize>,
+    error_counts: HashMap<String, usize>,
+}
+
+// A single completed request, captured for -csvFile output.
+struct RecordRow {
+    thread_id: usize,
+    index: usize,
+    status: String,
+    latency_ms: f64,
+    timestamp_ms: f64,
+}
+
+fn status_class(status: reqwest::StatusCode) -> &'static str {
+    match status.as_u16() {
+        100..=199 => "1xx",
+        200..=299 => "2xx",
+        300..=399 => "3xx",
+        400..=499 => "4xx",
+        _ => "5xx",
+    }
+}
+
+fn error_kind(err: &reqwest::Error) -> &'static str {
+    if err.is_timeout() {
+        "timeout"
+    } else if err.is_connect() {
+        "connect"
+    } else if err.is_body() {
+        "body"
+    } else if err.is_decode() {
+        "decode"
+    } else if err.is_redirect() {
+        "redirect"
+    } else if err.is_request() {
+        "request"
+    } else {
+        "other"
+    }
+}
+
+async fn fetch_data(client: Client, response_times: Arc<Mutex<Vec<f64>>>,
+                    status_counts: Arc<Mutex<HashMap<&'static str, usize>>>,
+                    error_counts: Arc<Mutex<HashMap<&'static str, usize>>>,
+                    spec: Arc<RequestSpec>,
+                    qps_interval_nanos: Option<u64>, next_send: Arc<AtomicU64>, base_time: Instant,
+                    deadline: Option<(Arc<AtomicBool>, Instant)>,
+                    records: Option<Arc<Mutex<Vec<RecordRow>>>>,
+                    url: String, sleep_time: Duration, keep_connects_open: bool,
                     thread_id: usize, num_calls: usize) {
-    for i in 0..num_calls {
+    let mut i = 0;
+    loop {
+        match &deadline {
+            Some((stop, deadline)) => {
+                if stop.load(Ordering::Relaxed) || Instant::now() >= *deadline {
+                    break;
+                }
+            }
+            None => {
+                if i >= num_calls {
+                    break;
+                }
+            }
+        }
+
+        if let Some(dt) = qps_interval_nanos {
+            let slot = next_send.fetch_add(dt, Ordering::Relaxed);
+            let target = base_time + Duration::from_nanos(slot);
+            tokio::time::sleep_until(tokio::time::Instant::from_std(target)).await;
+        }
+
+        let mut request = client.request(spec.method.clone(), &url).headers(spec.headers.clone());
+        if let Some(body) = &spec.body {
+            request = request.body(body.clone());
+        }
+
         let start_time = Instant::now();
-        let response = client.get(&url).send().await;
+        let response = request.send().await;
         let end_time = Instant::now();
 
         let response_time = (end_time - start_time).as_secs_f64() * 1000.0;
@@ -35,19 +146,33 @@ async fn fetch_data(client: Client, response_times: Arc<Mutex<Vec<f64>>>, url: S
         match response {
             Ok(resp) => {
                 status = resp.status().to_string();
+                *status_counts.lock().unwrap().entry(status_class(resp.status())).or_insert(0) += 1;
                 if !keep_connects_open {
                     let _ = resp.bytes().await;
                 }
                 println!("Thread {:2}.{:<6} - Success: {} - Response time: {:.2} ms", thread_id, i, status, response_time);
             }
             Err(err) => {
+                status = error_kind(&err).to_string();
+                *error_counts.lock().unwrap().entry(error_kind(&err)).or_insert(0) += 1;
                 println!("Thread {:2}.{:<6} - Request failed: {} - Response time: {:.2} ms", thread_id, i, err, response_time);
             }
         }
 
         response_times.lock().unwrap().push(response_time);
 
+        if let Some(records) = &records {
+            records.lock().unwrap().push(RecordRow {
+                thread_id,
+                index: i,
+                status,
+                latency_ms: response_time,
+                timestamp_ms: (start_time - base_time).as_secs_f64() * 1000.0,
+            });
+        }
+
         tokio::time::sleep(sleep_time).await;
+        i += 1;
     }
 }
 
@@ -80,6 +205,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut connect_timeout = Duration::from_millis(30000);
     let mut reuse_connects = false;
     let mut keep_connects_open = false;
+    let mut method = Method::GET;
+    let mut body: Option<Vec<u8>> = None;
+    let mut content_type: Option<String> = None;
+    let mut custom_headers: Vec<String> = Vec::new();
+    let mut qps: Option<f64> = None;
+    let mut duration: Option<u64> = None;
+    let mut output: Option<String> = None;
+    let mut csv_file: Option<String> = None;
 
     let mut i = 2;
     while i < args.len() {
@@ -112,6 +245,49 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 keep_connects_open = true;
                 i += 1;
             }
+            "-method" => {
+                method = Method::from_bytes(args[i + 1].to_uppercase().as_bytes()).expect("Invalid HTTP method");
+                i += 2;
+            }
+            "-body" => {
+                body = Some(args[i + 1].clone().into_bytes());
+                i += 2;
+            }
+            "-bodyFile" => {
+                body = Some(std::fs::read(&args[i + 1]).expect("Unable to read bodyFile"));
+                i += 2;
+            }
+            "-contentType" => {
+                content_type = Some(args[i + 1].clone());
+                i += 2;
+            }
+            "-header" => {
+                custom_headers.push(args[i + 1].clone());
+                i += 2;
+            }
+            "-qps" => {
+                qps = Some(args[i + 1].parse().expect("Invalid number for qps"));
+                i += 2;
+            }
+            "-duration" => {
+                duration = Some(args[i + 1].parse().expect("Invalid integer for duration"));
+                i += 2;
+            }
+            "-output" => {
+                output = Some(args[i + 1].to_lowercase());
+                i += 2;
+            }
+            "-csvFile" => {
+                csv_file = Some(args[i + 1].clone());
+                i += 2;
+            }
+            "-traceConn" => {
+                // Deferred: reqwest's high-level Client does not expose DNS/connect/TTFB
+                // phases, and the honest fix needs a lower-level hyper client with a custom
+                // timestamping connector. Tracked as chunk0-6 until that lands.
+                eprintln!("Warning: -traceConn is not yet implemented and will be ignored.");
+                i += 1;
+            }
             _ => {
                 i += 1;
             }
@@ -139,19 +315,55 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let client = client_builder.build()?;
 
+    let mut spec_headers = header::HeaderMap::new();
+    if let Some(content_type) = content_type {
+        spec_headers.insert(header::CONTENT_TYPE, content_type.parse().expect("Invalid contentType value"));
+    }
+    for raw in &custom_headers {
+        let (name, value) = raw.split_once(':').expect("Header must be in 'Name: Value' form");
+        let name: header::HeaderName = name.trim().parse().expect("Invalid header name");
+        let value = header::HeaderValue::from_str(value.trim()).expect("Invalid header value");
+        spec_headers.append(name, value);
+    }
+
+    let spec = Arc::new(RequestSpec { method, headers: spec_headers, body });
+
     let response_times = Arc::new(Mutex::new(Vec::new()));
+    let status_counts = Arc::new(Mutex::new(HashMap::new()));
+    let error_counts = Arc::new(Mutex::new(HashMap::new()));
+    let records = csv_file.as_ref().map(|_| Arc::new(Mutex::new(Vec::new())));
     let mut handles = Vec::new();
 
+    let qps_interval_nanos = qps.map(|q| (1_000_000_000.0 / q) as u64);
+    let next_send = Arc::new(AtomicU64::new(0));
+
     let start_time = Instant::now();
 
+    let deadline = duration.map(|secs| {
+        let stop = Arc::new(AtomicBool::new(false));
+        let deadline = start_time + Duration::from_secs(secs);
+        let watcher_stop = Arc::clone(&stop);
+        tokio::spawn(async move {
+            tokio::time::sleep_until(tokio::time::Instant::from_std(deadline)).await;
+            watcher_stop.store(true, Ordering::Relaxed);
+        });
+        (stop, deadline)
+    });
+
     for i in 0..num_threads {
         let num_calls = total_calls / num_threads + if i < total_calls % num_threads { 1 } else { 0 };
         let client = client.clone();
         let response_times = Arc::clone(&response_times);
+        let status_counts = Arc::clone(&status_counts);
+        let error_counts = Arc::clone(&error_counts);
+        let spec = Arc::clone(&spec);
+        let next_send = Arc::clone(&next_send);
+        let deadline = deadline.clone();
+        let records = records.clone();
         let url = url.to_string();
 
         handles.push(tokio::spawn(async move {
-            fetch_data(client, response_times, url, sleep_time, keep_connects_open, i, num_calls).await;
+            fetch_data(client, response_times, status_counts, error_counts, spec, qps_interval_nanos, next_send, start_time, deadline, records, url, sleep_time, keep_connects_open, i, num_calls).await;
         }));
     }
 
@@ -160,16 +372,231 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let end_time = Instant::now();
     let total_time = (end_time - start_time).as_secs_f64();
 
-    let requests_per_second = total_calls as f64 / total_time;
+    let mut response_times = response_times.lock().unwrap().clone();
 
-    let response_times = response_times.lock().unwrap();
-    let average_response_time = response_times.iter().sum::<f64>() / response_times.len() as f64;
+    let completed_calls = response_times.len();
+    let requests_per_second = completed_calls as f64 / total_time;
 
     println!("Total test time: {:.2} s", total_time);
-    println!("Average response time: {:.2} ms", average_response_time);
+    println!("Completed calls: {}", completed_calls);
     println!("Average requests per second: {:.2}", requests_per_second);
+    print_latency_summary(&mut response_times);
+    print_status_summary(&status_counts.lock().unwrap(), &error_counts.lock().unwrap());
+
+    if output.is_some() || csv_file.is_some() {
+        let summary = Summary {
+            total_time_s: total_time,
+            completed_calls,
+            requests_per_second,
+            latency: compute_latency_stats(&response_times),
+            status_counts: status_counts.lock().unwrap().iter().map(|(k, v)| (k.to_string(), *v)).collect(),
+            error_counts: error_counts.lock().unwrap().iter().map(|(k, v)| (k.to_string(), *v)).collect(),
+        };
+
+        match output.as_deref() {
+            Some("json") => println!("{}", serde_json::to_string_pretty(&summary)?),
+            Some("csv") => print_summary_csv(&summary),
+            Some(other) => println!("Error: unknown output format \"{}\"", other),
+            None => {}
+        }
+
+        if let (Some(path), Some(records)) = (&csv_file, &records) {
+            write_records_csv(path, &records.lock().unwrap())?;
+            println!("Wrote per-request records to {}", path);
+        }
+    }
 
     println!("All threads have finished.");
 
     Ok(())
+}
+
+fn compute_latency_stats(sorted: &[f64]) -> Option<LatencyStats> {
+    let n = sorted.len();
+    if n == 0 {
+        return None;
+    }
+
+    let mean = sorted.iter().sum::<f64>() / n as f64;
+    let variance = sorted.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / n as f64;
+
+    Some(LatencyStats {
+        min: sorted[0],
+        mean,
+        stddev: variance.sqrt(),
+        max: sorted[n - 1],
+        p50: percentile(sorted, 50.0),
+        p90: percentile(sorted, 90.0),
+        p95: percentile(sorted, 95.0),
+        p99: percentile(sorted, 99.0),
+        p999: percentile(sorted, 99.9),
+    })
+}
+
+fn print_summary_csv(summary: &Summary) {
+    println!("metric,value");
+    println!("total_time_s,{:.6}", summary.total_time_s);
+    println!("completed_calls,{}", summary.completed_calls);
+    println!("requests_per_second,{:.6}", summary.requests_per_second);
+    if let Some(l) = &summary.latency {
+        println!("min_ms,{:.6}", l.min);
+        println!("mean_ms,{:.6}", l.mean);
+        println!("stddev_ms,{:.6}", l.stddev);
+        println!("max_ms,{:.6}", l.max);
+        println!("p50_ms,{:.6}", l.p50);
+        println!("p90_ms,{:.6}", l.p90);
+        println!("p95_ms,{:.6}", l.p95);
+        println!("p99_ms,{:.6}", l.p99);
+        println!("p999_ms,{:.6}", l.p999);
+    }
+    for (class, count) in &summary.status_counts {
+        println!("status_{},{}", class, count);
+    }
+    for (kind, count) in &summary.error_counts {
+        println!("error_{},{}", kind, count);
+    }
+}
+
+fn write_records_csv(path: &str, records: &[RecordRow]) -> std::io::Result<()> {
+    use std::io::Write;
+    let mut file = std::fs::File::create(path)?;
+    writeln!(file, "thread_id,index,status,latency_ms,timestamp_ms")?;
+    for r in records {
+        writeln!(file, "{},{},{},{:.6},{:.6}", r.thread_id, r.index, r.status, r.latency_ms, r.timestamp_ms)?;
+    }
+    Ok(())
+}
+
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let n = sorted.len();
+    let i = ((p / 100.0) * n as f64).ceil() as usize;
+    let i = i.saturating_sub(1).min(n - 1);
+    sorted[i]
+}
+
+fn print_latency_summary(response_times: &mut Vec<f64>) {
+    if response_times.is_empty() {
+        println!("No response times were recorded.");
+        return;
+    }
+
+    response_times.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    let n = response_times.len();
+    let min = response_times[0];
+    let max = response_times[n - 1];
+    let mean = response_times.iter().sum::<f64>() / n as f64;
+    let variance = response_times.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / n as f64;
+    let std_dev = variance.sqrt();
+
+    println!("Latency distribution ({} samples):", n);
+    println!("  min:    {:.2} ms", min);
+    println!("  mean:   {:.2} ms", mean);
+    println!("  stddev: {:.2} ms", std_dev);
+    println!("  max:    {:.2} ms", max);
+    println!("  p50:    {:.2} ms", percentile(response_times, 50.0));
+    println!("  p90:    {:.2} ms", percentile(response_times, 90.0));
+    println!("  p95:    {:.2} ms", percentile(response_times, 95.0));
+    println!("  p99:    {:.2} ms", percentile(response_times, 99.0));
+    println!("  p99.9:  {:.2} ms", percentile(response_times, 99.9));
+
+    print_latency_histogram(response_times, min, max);
+}
+
+fn print_status_summary(status_counts: &HashMap<&'static str, usize>,
+                        error_counts: &HashMap<&'static str, usize>) {
+    let mut parts: Vec<String> = Vec::new();
+    for class in ["1xx", "2xx", "3xx", "4xx", "5xx"] {
+        if let Some(&count) = status_counts.get(class) {
+            parts.push(format!("{}: {}", class, count));
+        }
+    }
+
+    let mut error_kinds: Vec<(&&str, &usize)> = error_counts.iter().collect();
+    error_kinds.sort_by_key(|(kind, _)| **kind);
+    for (kind, count) in error_kinds {
+        parts.push(format!("{}s: {}", kind, count));
+    }
+
+    if parts.is_empty() {
+        println!("Status distribution: none");
+    } else {
+        println!("Status distribution: {}", parts.join("  "));
+    }
+}
+
+fn print_latency_histogram(sorted: &[f64], min: f64, max: f64) {
+    const BUCKETS: usize = 10;
+    let span = max - min;
+
+    let mut counts = [0usize; BUCKETS];
+    for &t in sorted {
+        let bucket = if span > 0.0 {
+            (((t - min) / span) * BUCKETS as f64).floor() as usize
+        } else {
+            0
+        };
+        counts[bucket.min(BUCKETS - 1)] += 1;
+    }
+
+    let peak = counts.iter().copied().max().unwrap_or(0);
+    let width = span / BUCKETS as f64;
+
+    println!("Latency histogram:");
+    for (i, &count) in counts.iter().enumerate() {
+        let lo = min + width * i as f64;
+        let hi = lo + width;
+        let bar_len = if peak > 0 { count * 50 / peak } else { 0 };
+        let bar: String = "#".repeat(bar_len);
+        println!("  {:8.2} - {:8.2} ms [{:6}] {}", lo, hi, count, bar);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentile_indices() {
+        let sorted: Vec<f64> = (1..=10).map(|v| v as f64).collect();
+        assert_eq!(percentile(&sorted, 50.0), 5.0);
+        assert_eq!(percentile(&sorted, 90.0), 9.0);
+        assert_eq!(percentile(&sorted, 99.0), 10.0);
+        assert_eq!(percentile(&sorted, 99.9), 10.0);
+    }
+
+    #[test]
+    fn percentile_single_sample() {
+        let sorted = [42.0];
+        assert_eq!(percentile(&sorted, 50.0), 42.0);
+        assert_eq!(percentile(&sorted, 99.9), 42.0);
+    }
+
+    #[test]
+    fn latency_stats_empty_and_single() {
+        assert!(compute_latency_stats(&[]).is_none());
+
+        let stats = compute_latency_stats(&[42.0]).unwrap();
+        assert_eq!(stats.min, 42.0);
+        assert_eq!(stats.max, 42.0);
+        assert_eq!(stats.mean, 42.0);
+        assert_eq!(stats.stddev, 0.0);
+        assert_eq!(stats.p50, 42.0);
+        assert_eq!(stats.p999, 42.0);
+    }
+
+    #[test]
+    fn latency_stats_percentiles() {
+        let sorted: Vec<f64> = (1..=10).map(|v| v as f64).collect();
+        let stats = compute_latency_stats(&sorted).unwrap();
+        assert_eq!(stats.p50, 5.0);
+        assert_eq!(stats.p99, 10.0);
+        assert_eq!(stats.p999, 10.0);
+    }
+
+    #[test]
+    fn histogram_single_value_does_not_panic() {
+        // span == 0: every sample falls in bucket 0 rather than dividing by zero.
+        print_latency_histogram(&[5.0, 5.0, 5.0], 5.0, 5.0);
+    }
 }
\ No newline at end of file